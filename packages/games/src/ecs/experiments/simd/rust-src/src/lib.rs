@@ -1,3 +1,9 @@
+#![feature(portable_simd)]
+
+use std::collections::HashMap;
+use std::simd::f32x4;
+use std::simd::num::SimdFloat;
+use std::simd::StdFloat;
 use wasm_bindgen::prelude::*;
 
 // Import the `console.log` function from the `console` module
@@ -12,6 +18,96 @@ macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
+// A single cell of the spatial hash grid, holding the indices of the
+// entities currently inside it.
+#[derive(Default)]
+struct GridBin {
+    entities: Vec<usize>,
+}
+
+// Uniform spatial hash grid used to turn collision/query from O(n^2) into
+// roughly O(n) for uniformly distributed entities. Entities are bucketed by
+// `cell_size`-sided cells; `update` moves an entity between bins only when
+// it crosses a cell boundary instead of rebuilding the whole grid.
+struct SpatialGrid {
+    cell_size: f32,
+    bins: HashMap<(i32, i32), GridBin>,
+    entity_cell: Vec<(i32, i32)>,
+}
+
+impl SpatialGrid {
+    fn new(cell_size: f32) -> Self {
+        SpatialGrid {
+            cell_size,
+            bins: HashMap::new(),
+            entity_cell: Vec::new(),
+        }
+    }
+
+    fn cell_of(&self, x: f32, y: f32) -> (i32, i32) {
+        ((x / self.cell_size).floor() as i32, (y / self.cell_size).floor() as i32)
+    }
+
+    fn clear(&mut self) {
+        self.bins.clear();
+        self.entity_cell.clear();
+    }
+
+    fn insert(&mut self, index: usize, x: f32, y: f32) {
+        let cell = self.cell_of(x, y);
+        self.bins.entry(cell).or_default().entities.push(index);
+        if index >= self.entity_cell.len() {
+            self.entity_cell.resize(index + 1, cell);
+        }
+        self.entity_cell[index] = cell;
+    }
+
+    // Move `index` to its new cell only if it actually crossed a boundary.
+    fn update(&mut self, index: usize, x: f32, y: f32) {
+        let new_cell = self.cell_of(x, y);
+        let old_cell = self.entity_cell[index];
+        if new_cell == old_cell {
+            return;
+        }
+        if let Some(bin) = self.bins.get_mut(&old_cell) {
+            bin.entities.retain(|&e| e != index);
+        }
+        self.bins.entry(new_cell).or_default().entities.push(index);
+        self.entity_cell[index] = new_cell;
+    }
+
+    fn rebuild(&mut self, positions: &[f32], entity_count: usize) {
+        self.clear();
+        for i in 0..entity_count {
+            self.insert(i, positions[i * 2], positions[i * 2 + 1]);
+        }
+    }
+
+    // How many cells out from `cell` must be scanned to guarantee every
+    // entity within `radius` is covered. A single ring (3x3) only covers
+    // entities up to one cell_size away; once `radius` exceeds that, a
+    // fixed 3x3 window silently drops real hits, so the ring width is
+    // derived from `radius` instead of assumed to be 1.
+    fn ring_for_radius(&self, radius: f32) -> i32 {
+        if self.cell_size <= 0.0 {
+            return 1;
+        }
+        (radius / self.cell_size).ceil().max(1.0) as i32
+    }
+
+    // Candidate entities for a `radius` query around `cell`: every entity in
+    // as many rings of cells as `radius` requires (at least the immediate
+    // 3x3 neighborhood). Not filtered by distance; callers do that.
+    fn neighbors_within(&self, cell: (i32, i32), radius: f32) -> impl Iterator<Item = &usize> {
+        let ring = self.ring_for_radius(radius);
+        let (cx, cy) = cell;
+        (-ring..=ring)
+            .flat_map(move |dx| (-ring..=ring).map(move |dy| (cx + dx, cy + dy)))
+            .filter_map(move |c| self.bins.get(&c))
+            .flat_map(|bin| bin.entities.iter())
+    }
+}
+
 // SIMD operations for position updates
 #[wasm_bindgen]
 pub struct PositionSystemSIMD {
@@ -20,6 +116,7 @@ pub struct PositionSystemSIMD {
     accelerations: Vec<f32>,
     masses: Vec<f32>,
     entity_count: usize,
+    spatial_grid: SpatialGrid,
 }
 
 #[wasm_bindgen]
@@ -34,9 +131,23 @@ impl PositionSystemSIMD {
             accelerations: vec![0.0; max_entities * 2],
             masses: vec![1.0; max_entities],
             entity_count: 0,
+            spatial_grid: SpatialGrid::new(32.0),
         }
     }
 
+    // Sets the spatial hash grid's cell side length and rebuilds it from
+    // scratch. For best performance keep this roughly 2x the radius you
+    // query/detect collisions with: `detect_collisions`, `spatial_query`,
+    // and `apply_flocking` all widen their cell scan to cover whatever
+    // radius is passed, so correctness does not depend on this value as
+    // long as it is positive, but a `cell_size` much smaller than the
+    // query radius means scanning many cells per entity.
+    #[wasm_bindgen]
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        self.spatial_grid = SpatialGrid::new(cell_size);
+        self.spatial_grid.rebuild(&self.positions, self.entity_count);
+    }
+
     #[wasm_bindgen]
     pub fn add_entity(&mut self, x: f32, y: f32, vx: f32, vy: f32, ax: f32, ay: f32, mass: f32) -> usize {
         let index = self.entity_count;
@@ -60,65 +171,51 @@ impl PositionSystemSIMD {
         self.masses[index] = mass;
 
         self.entity_count += 1;
+        self.spatial_grid.insert(index, x, y);
         index
     }
 
     #[wasm_bindgen]
     pub fn update_positions(&mut self, delta_time: f32) {
-        // SIMD-optimized position update
-        // Process 4 entities at a time using SIMD operations
-        let entity_count = self.entity_count;
-        let positions = &mut self.positions;
-        let velocities = &self.velocities;
-
-        // Process in chunks of 4 for SIMD optimization
-        for i in (0..entity_count * 2).step_by(8) { // 8 because we process 4 entities (2 floats each)
-            if i + 7 < entity_count * 2 {
-                // SIMD-style processing: process 4 positions at once
-                positions[i] += velocities[i] * delta_time;
-                positions[i + 1] += velocities[i + 1] * delta_time;
-                positions[i + 2] += velocities[i + 2] * delta_time;
-                positions[i + 3] += velocities[i + 3] * delta_time;
-                positions[i + 4] += velocities[i + 4] * delta_time;
-                positions[i + 5] += velocities[i + 5] * delta_time;
-                positions[i + 6] += velocities[i + 6] * delta_time;
-                positions[i + 7] += velocities[i + 7] * delta_time;
-            } else {
-                // Handle remaining elements
-                for j in i..entity_count * 2 {
-                    positions[j] += velocities[j] * delta_time;
-                }
-                break;
-            }
+        // Real SIMD: pos += vel * dt as a vector multiply and add (not
+        // fused) over f32x4 lanes, with a scalar tail for the remainder.
+        let len = self.entity_count * 2;
+        let dt = f32x4::splat(delta_time);
+        let chunks = len / 4;
+
+        for c in 0..chunks {
+            let i = c * 4;
+            let pos = f32x4::from_slice(&self.positions[i..i + 4]);
+            let vel = f32x4::from_slice(&self.velocities[i..i + 4]);
+            (pos + vel * dt).copy_to_slice(&mut self.positions[i..i + 4]);
+        }
+
+        for j in (chunks * 4)..len {
+            self.positions[j] += self.velocities[j] * delta_time;
+        }
+
+        for i in 0..self.entity_count {
+            self.spatial_grid.update(i, self.positions[i * 2], self.positions[i * 2 + 1]);
         }
     }
 
     #[wasm_bindgen]
     pub fn update_velocities(&mut self, delta_time: f32) {
-        // SIMD-optimized velocity update
-        let entity_count = self.entity_count;
-        let velocities = &mut self.velocities;
-        let accelerations = &self.accelerations;
-
-        // Process in chunks of 4 for SIMD optimization
-        for i in (0..entity_count * 2).step_by(8) {
-            if i + 7 < entity_count * 2 {
-                // SIMD-style processing: process 4 velocities at once
-                velocities[i] += accelerations[i] * delta_time;
-                velocities[i + 1] += accelerations[i + 1] * delta_time;
-                velocities[i + 2] += accelerations[i + 2] * delta_time;
-                velocities[i + 3] += accelerations[i + 3] * delta_time;
-                velocities[i + 4] += accelerations[i + 4] * delta_time;
-                velocities[i + 5] += accelerations[i + 5] * delta_time;
-                velocities[i + 6] += accelerations[i + 6] * delta_time;
-                velocities[i + 7] += accelerations[i + 7] * delta_time;
-            } else {
-                // Handle remaining elements
-                for j in i..entity_count * 2 {
-                    velocities[j] += accelerations[j] * delta_time;
-                }
-                break;
-            }
+        // Real SIMD: vel += acc * dt as a vector multiply and add (not
+        // fused) over f32x4 lanes, with a scalar tail for the remainder.
+        let len = self.entity_count * 2;
+        let dt = f32x4::splat(delta_time);
+        let chunks = len / 4;
+
+        for c in 0..chunks {
+            let i = c * 4;
+            let vel = f32x4::from_slice(&self.velocities[i..i + 4]);
+            let acc = f32x4::from_slice(&self.accelerations[i..i + 4]);
+            (vel + acc * dt).copy_to_slice(&mut self.velocities[i..i + 4]);
+        }
+
+        for j in (chunks * 4)..len {
+            self.velocities[j] += self.accelerations[j] * delta_time;
         }
     }
 
@@ -140,16 +237,185 @@ impl PositionSystemSIMD {
         }
     }
 
+    // All-pairs gravitational N-body integration using the stored `masses`.
+    // Each pair (i, j) is computed once and the resulting acceleration
+    // applied to both bodies via Newton's third law; `softening` avoids the
+    // singularity when two bodies nearly overlap. The inner accumulation is
+    // vectorized with f32x4 over batches of target bodies, with a scalar
+    // tail for the remainder.
+    #[wasm_bindgen]
+    pub fn apply_gravity(&mut self, g: f32, softening: f32) {
+        let entity_count = self.entity_count;
+        let softening_squared = softening * softening;
+        let mut acc_x = vec![0.0f32; entity_count];
+        let mut acc_y = vec![0.0f32; entity_count];
+
+        for i in 0..entity_count {
+            let pos_ix = self.positions[i * 2];
+            let pos_iy = self.positions[i * 2 + 1];
+            let mass_i = self.masses[i];
+
+            let start = i + 1;
+            let chunk_count = (entity_count - start) / 4;
+
+            for c in 0..chunk_count {
+                let base = start + c * 4;
+                let mut dx_lanes = [0.0f32; 4];
+                let mut dy_lanes = [0.0f32; 4];
+                let mut mass_lanes = [0.0f32; 4];
+                for k in 0..4 {
+                    let j = base + k;
+                    dx_lanes[k] = self.positions[j * 2] - pos_ix;
+                    dy_lanes[k] = self.positions[j * 2 + 1] - pos_iy;
+                    mass_lanes[k] = self.masses[j];
+                }
+
+                let dx = f32x4::from_array(dx_lanes);
+                let dy = f32x4::from_array(dy_lanes);
+                let mass_j = f32x4::from_array(mass_lanes);
+
+                let dist2 = dx * dx + dy * dy + f32x4::splat(softening_squared);
+                let inv_dist3 = f32x4::splat(1.0) / (dist2 * dist2.sqrt());
+                let factor = f32x4::splat(g) * inv_dist3;
+                let fx = factor * dx;
+                let fy = factor * dy;
+
+                acc_x[i] += (fx * mass_j).reduce_sum();
+                acc_y[i] += (fy * mass_j).reduce_sum();
+
+                let fx_lanes = fx.to_array();
+                let fy_lanes = fy.to_array();
+                for k in 0..4 {
+                    let j = base + k;
+                    acc_x[j] -= fx_lanes[k] * mass_i;
+                    acc_y[j] -= fy_lanes[k] * mass_i;
+                }
+            }
+
+            for j in (start + chunk_count * 4)..entity_count {
+                let dx = self.positions[j * 2] - pos_ix;
+                let dy = self.positions[j * 2 + 1] - pos_iy;
+                let dist2 = dx * dx + dy * dy + softening_squared;
+                let inv_dist3 = 1.0 / (dist2 * dist2.sqrt());
+                let factor = g * inv_dist3;
+
+                acc_x[i] += factor * dx * self.masses[j];
+                acc_y[i] += factor * dy * self.masses[j];
+                acc_x[j] -= factor * dx * mass_i;
+                acc_y[j] -= factor * dy * mass_i;
+            }
+        }
+
+        for i in 0..entity_count {
+            self.accelerations[i * 2] = acc_x[i];
+            self.accelerations[i * 2 + 1] = acc_y[i];
+        }
+    }
+
+    // Classic boids steering: separation, alignment and cohesion gathered
+    // from each entity's neighbors within `perception_radius` (via the
+    // spatial grid, which widens its cell scan to cover `perception_radius`
+    // regardless of `cell_size`), combined with the given weights and
+    // written into `accelerations`, with the resulting magnitude clamped to
+    // `max_speed`.
+    #[wasm_bindgen]
+    pub fn apply_flocking(
+        &mut self,
+        perception_radius: f32,
+        sep_weight: f32,
+        align_weight: f32,
+        cohesion_weight: f32,
+        max_speed: f32,
+    ) {
+        let perception_squared = perception_radius * perception_radius;
+
+        for i in 0..self.entity_count {
+            let pos_ix = self.positions[i * 2];
+            let pos_iy = self.positions[i * 2 + 1];
+            let vel_ix = self.velocities[i * 2];
+            let vel_iy = self.velocities[i * 2 + 1];
+
+            let (mut sep_x, mut sep_y) = (0.0, 0.0);
+            let (mut avg_vel_x, mut avg_vel_y) = (0.0, 0.0);
+            let (mut avg_pos_x, mut avg_pos_y) = (0.0, 0.0);
+            let mut neighbor_count = 0u32;
+
+            let cell = self.spatial_grid.entity_cell[i];
+            for &j in self.spatial_grid.neighbors_within(cell, perception_radius) {
+                if j == i {
+                    continue;
+                }
+
+                let dx = pos_ix - self.positions[j * 2];
+                let dy = pos_iy - self.positions[j * 2 + 1];
+                let distance_squared = dx * dx + dy * dy;
+                if distance_squared == 0.0 || distance_squared > perception_squared {
+                    continue;
+                }
+
+                // Weighted inversely by distance: dividing by distance_squared
+                // (rather than just normalizing to a unit vector) makes very
+                // close neighbors dominate the repulsion.
+                sep_x += dx / distance_squared;
+                sep_y += dy / distance_squared;
+
+                avg_vel_x += self.velocities[j * 2];
+                avg_vel_y += self.velocities[j * 2 + 1];
+
+                avg_pos_x += self.positions[j * 2];
+                avg_pos_y += self.positions[j * 2 + 1];
+
+                neighbor_count += 1;
+            }
+
+            if neighbor_count == 0 {
+                self.accelerations[i * 2] = 0.0;
+                self.accelerations[i * 2 + 1] = 0.0;
+                continue;
+            }
+
+            let count = neighbor_count as f32;
+            let alignment_x = avg_vel_x / count - vel_ix;
+            let alignment_y = avg_vel_y / count - vel_iy;
+            let cohesion_x = avg_pos_x / count - pos_ix;
+            let cohesion_y = avg_pos_y / count - pos_iy;
+
+            let mut ax = sep_x * sep_weight + alignment_x * align_weight + cohesion_x * cohesion_weight;
+            let mut ay = sep_y * sep_weight + alignment_y * align_weight + cohesion_y * cohesion_weight;
+
+            let speed = (ax * ax + ay * ay).sqrt();
+            if speed > max_speed && speed > 0.0 {
+                let scale = max_speed / speed;
+                ax *= scale;
+                ay *= scale;
+            }
+
+            self.accelerations[i * 2] = ax;
+            self.accelerations[i * 2 + 1] = ay;
+        }
+    }
+
+    // Two entities collide when within `2 * radius` of each other, so the
+    // grid is scanned out to that distance (not just the immediate 3x3
+    // cells) regardless of `cell_size`, finding the same pairs as a
+    // brute-force scan for any `radius`. Each pair is `(i, j)` with i < j,
+    // but pairs come out in grid-cell order, not sorted by index.
     #[wasm_bindgen]
     pub fn detect_collisions(&self, radius: f32) -> Vec<usize> {
         let mut collisions = Vec::new();
-        let entity_count = self.entity_count;
         let positions = &self.positions;
         let radius_squared = radius * radius;
+        let scan_radius = radius * 2.0;
+
+        // Requiring j > i both deduplicates the (i, j) pairs and skips
+        // self-comparisons.
+        for i in 0..self.entity_count {
+            let cell = self.spatial_grid.entity_cell[i];
+            for &j in self.spatial_grid.neighbors_within(cell, scan_radius) {
+                if j <= i {
+                    continue;
+                }
 
-        // Optimized collision detection with early termination
-        for i in 0..entity_count {
-            for j in (i + 1)..entity_count {
                 let pos1_index = i * 2;
                 let pos2_index = j * 2;
 
@@ -167,14 +433,19 @@ impl PositionSystemSIMD {
         collisions
     }
 
+    // Hashes the query point to a cell and scans entities in the cells
+    // overlapping `radius` around it; the scan widens automatically when
+    // `radius` exceeds `cell_size` so it finds the same entities as a
+    // brute-force scan for any `radius`, in grid-cell order rather than
+    // index order.
     #[wasm_bindgen]
     pub fn spatial_query(&self, query_x: f32, query_y: f32, radius: f32) -> Vec<usize> {
         let mut results = Vec::new();
-        let entity_count = self.entity_count;
         let positions = &self.positions;
         let radius_squared = radius * radius;
 
-        for i in 0..entity_count {
+        let cell = self.spatial_grid.cell_of(query_x, query_y);
+        for &i in self.spatial_grid.neighbors_within(cell, radius) {
             let pos_index = i * 2;
             let dx = positions[pos_index] - query_x;
             let dy = positions[pos_index + 1] - query_y;
@@ -208,6 +479,7 @@ impl PositionSystemSIMD {
     #[wasm_bindgen]
     pub fn clear(&mut self) {
         self.entity_count = 0;
+        self.spatial_grid.clear();
     }
 
     // Get raw data for benchmarking
@@ -230,64 +502,275 @@ impl PositionSystemSIMD {
     pub fn get_mass_data(&self) -> Vec<f32> {
         self.masses[..self.entity_count].to_vec()
     }
+
+    // Zero-copy views into the Wasm linear memory backing the SoA buffers.
+    //
+    // Build the JS-side view with e.g.
+    // `new Float32Array(memory.buffer, system.positions_ptr(), system.positions_len())`.
+    // The view aliases `self.positions` directly: it is invalidated the
+    // moment the backing `Vec` reallocates (e.g. `max_entities` growing, or
+    // the `PositionSystemSIMD` being dropped), since that can move the
+    // memory to a different address or free it outright. Re-fetch the
+    // pointer and rebuild the view after any call that could grow storage;
+    // do not hold it across such calls. The copying `get_*_data` getters
+    // above remain the safe option for callers that can't uphold that
+    // contract.
+    #[wasm_bindgen]
+    pub fn positions_ptr(&self) -> *const f32 {
+        self.positions.as_ptr()
+    }
+
+    #[wasm_bindgen]
+    pub fn positions_len(&self) -> usize {
+        self.entity_count * 2
+    }
+
+    #[wasm_bindgen]
+    pub fn velocities_ptr(&self) -> *const f32 {
+        self.velocities.as_ptr()
+    }
+
+    #[wasm_bindgen]
+    pub fn velocities_len(&self) -> usize {
+        self.entity_count * 2
+    }
+
+    #[wasm_bindgen]
+    pub fn accelerations_ptr(&self) -> *const f32 {
+        self.accelerations.as_ptr()
+    }
+
+    #[wasm_bindgen]
+    pub fn accelerations_len(&self) -> usize {
+        self.entity_count * 2
+    }
+
+    #[wasm_bindgen]
+    pub fn masses_ptr(&self) -> *const f32 {
+        self.masses.as_ptr()
+    }
+
+    #[wasm_bindgen]
+    pub fn masses_len(&self) -> usize {
+        self.entity_count
+    }
 }
 
 // Pure SIMD operations for direct comparison
 #[wasm_bindgen]
 pub fn simd_vector_add(a: &[f32], b: &[f32], result: &mut [f32]) {
-    // SIMD-optimized vector addition
-    // Process 4 elements at a time
-    for i in (0..a.len()).step_by(4) {
-        if i + 3 < a.len() {
-            result[i] = a[i] + b[i];
-            result[i + 1] = a[i + 1] + b[i + 1];
-            result[i + 2] = a[i + 2] + b[i + 2];
-            result[i + 3] = a[i + 3] + b[i + 3];
-        } else {
-            // Handle remaining elements
-            for j in i..a.len() {
-                result[j] = a[j] + b[j];
-            }
-            break;
-        }
+    // Real SIMD: add f32x4 lanes, scalar fallback for the remainder tail.
+    let chunks = a.len() / 4;
+
+    for c in 0..chunks {
+        let i = c * 4;
+        let va = f32x4::from_slice(&a[i..i + 4]);
+        let vb = f32x4::from_slice(&b[i..i + 4]);
+        (va + vb).copy_to_slice(&mut result[i..i + 4]);
+    }
+
+    for j in (chunks * 4)..a.len() {
+        result[j] = a[j] + b[j];
     }
 }
 
 #[wasm_bindgen]
 pub fn simd_vector_multiply(a: &[f32], scalar: f32, result: &mut [f32]) {
-    // SIMD-optimized vector scalar multiplication
-    for i in (0..a.len()).step_by(4) {
-        if i + 3 < a.len() {
-            result[i] = a[i] * scalar;
-            result[i + 1] = a[i + 1] * scalar;
-            result[i + 2] = a[i + 2] * scalar;
-            result[i + 3] = a[i + 3] * scalar;
-        } else {
-            // Handle remaining elements
-            for j in i..a.len() {
-                result[j] = a[j] * scalar;
-            }
-            break;
-        }
+    // Real SIMD: multiply f32x4 lanes by a splatted scalar, scalar fallback for the tail.
+    let factor = f32x4::splat(scalar);
+    let chunks = a.len() / 4;
+
+    for c in 0..chunks {
+        let i = c * 4;
+        let va = f32x4::from_slice(&a[i..i + 4]);
+        (va * factor).copy_to_slice(&mut result[i..i + 4]);
+    }
+
+    for j in (chunks * 4)..a.len() {
+        result[j] = a[j] * scalar;
     }
 }
 
 #[wasm_bindgen]
 pub fn simd_dot_product(a: &[f32], b: &[f32]) -> f32 {
-    let mut sum = 0.0;
-    
-    // SIMD-optimized dot product
-    for i in (0..a.len()).step_by(4) {
-        if i + 3 < a.len() {
-            sum += a[i] * b[i] + a[i + 1] * b[i + 1] + a[i + 2] * b[i + 2] + a[i + 3] * b[i + 3];
-        } else {
-            // Handle remaining elements
-            for j in i..a.len() {
-                sum += a[j] * b[j];
-            }
-            break;
-        }
+    // Real SIMD: accumulate into an f32x4 partial-sum vector, reduce once at the end.
+    let mut acc = f32x4::splat(0.0);
+    let chunks = a.len() / 4;
+
+    for c in 0..chunks {
+        let i = c * 4;
+        let va = f32x4::from_slice(&a[i..i + 4]);
+        let vb = f32x4::from_slice(&b[i..i + 4]);
+        acc += va * vb;
     }
-    
+
+    let mut sum = acc.reduce_sum();
+    for j in (chunks * 4)..a.len() {
+        sum += a[j] * b[j];
+    }
+
     sum
 }
+
+// These exercise the plain SIMD helpers and the position/velocity update
+// loops directly, sidestepping `PositionSystemSIMD::new`'s console_log!
+// call (a wasm-bindgen extern that panics off wasm32) by building the
+// struct with a literal instead. That lets them run under a plain native
+// `cargo test`, which is exactly where off-by-one bugs in the tail-
+// remainder chunking would show up.
+#[cfg(test)]
+mod native_tests {
+    use super::*;
+
+    #[test]
+    fn simd_vector_add_handles_remainder_tail() {
+        // Length 5 is not a multiple of 4, exercising the scalar tail.
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [10.0, 20.0, 30.0, 40.0, 50.0];
+        let mut result = [0.0; 5];
+
+        simd_vector_add(&a, &b, &mut result);
+
+        assert_eq!(result, [11.0, 22.0, 33.0, 44.0, 55.0]);
+    }
+
+    #[test]
+    fn simd_vector_multiply_handles_remainder_tail() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let mut result = [0.0; 5];
+
+        simd_vector_multiply(&a, 2.0, &mut result);
+
+        assert_eq!(result, [2.0, 4.0, 6.0, 8.0, 10.0]);
+    }
+
+    #[test]
+    fn simd_dot_product_handles_remainder_tail() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [1.0, 1.0, 1.0, 1.0, 1.0];
+
+        assert_eq!(simd_dot_product(&a, &b), 15.0);
+    }
+
+    #[test]
+    fn update_positions_handles_remainder_tail() {
+        // 3 entities = 6 floats, not a multiple of 4.
+        let positions = vec![0.0, 0.0, 1.0, 1.0, 2.0, 2.0];
+        let mut spatial_grid = SpatialGrid::new(32.0);
+        spatial_grid.rebuild(&positions, 3);
+        let mut system = PositionSystemSIMD {
+            positions,
+            velocities: vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0],
+            accelerations: vec![0.0; 6],
+            masses: vec![1.0; 3],
+            entity_count: 3,
+            spatial_grid,
+        };
+
+        system.update_positions(1.0);
+
+        assert_eq!(system.positions, vec![1.0, 1.0, 2.0, 2.0, 3.0, 3.0]);
+    }
+
+    #[test]
+    fn update_velocities_handles_remainder_tail() {
+        // 3 entities = 6 floats, not a multiple of 4.
+        let mut system = PositionSystemSIMD {
+            positions: vec![0.0; 6],
+            velocities: vec![0.0, 0.0, 1.0, 1.0, 2.0, 2.0],
+            accelerations: vec![1.0; 6],
+            masses: vec![1.0; 3],
+            entity_count: 3,
+            spatial_grid: SpatialGrid::new(32.0),
+        };
+
+        system.update_velocities(1.0);
+
+        assert_eq!(system.velocities, vec![1.0, 1.0, 2.0, 2.0, 3.0, 3.0]);
+    }
+}
+
+// `PositionSystemSIMD::new` logs through a wasm-bindgen imported extern,
+// which only resolves under the wasm32 target with its JS shims, so these
+// run via `wasm-bindgen-test` (e.g. `wasm-pack test --headless --chrome`)
+// rather than a plain native `cargo test`.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn detect_collisions_finds_pairs_spanning_multiple_cells() {
+        // Default cell_size is 32.0, so these two entities sit two cells
+        // apart: a fixed 3x3 scan would miss them even though they are a
+        // genuine collision at radius 50.0 (threshold distance 100.0).
+        let mut system = PositionSystemSIMD::new(8);
+        system.add_entity(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        system.add_entity(70.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+
+        assert_eq!(system.detect_collisions(50.0), vec![0, 1]);
+    }
+
+    #[wasm_bindgen_test]
+    fn spatial_query_finds_entities_beyond_one_cell() {
+        let mut system = PositionSystemSIMD::new(8);
+        system.add_entity(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        system.add_entity(70.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+
+        let mut hits = system.spatial_query(0.0, 0.0, 80.0);
+        hits.sort();
+        assert_eq!(hits, vec![0, 1]);
+    }
+
+    #[wasm_bindgen_test]
+    fn apply_flocking_separates_close_entities() {
+        let mut system = PositionSystemSIMD::new(8);
+        system.add_entity(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        system.add_entity(1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+
+        system.apply_flocking(10.0, 1.0, 0.0, 0.0, 100.0);
+
+        let acc = system.get_acceleration_data();
+        assert!(acc[0] < 0.0, "entity 0 should be pushed away from entity 1");
+        assert!(acc[2] > 0.0, "entity 1 should be pushed away from entity 0");
+    }
+
+    #[wasm_bindgen_test]
+    fn apply_flocking_separation_is_stronger_for_closer_neighbors() {
+        // Separation is weighted inversely by distance, so a neighbor right
+        // next to an entity should push much harder than one farther away
+        // inside perception_radius.
+        let mut close = PositionSystemSIMD::new(8);
+        close.add_entity(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        close.add_entity(1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        close.apply_flocking(20.0, 1.0, 0.0, 0.0, 1000.0);
+
+        let mut far = PositionSystemSIMD::new(8);
+        far.add_entity(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        far.add_entity(5.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        far.apply_flocking(20.0, 1.0, 0.0, 0.0, 1000.0);
+
+        let close_accel = close.get_acceleration_data()[0].abs();
+        let far_accel = far.get_acceleration_data()[0].abs();
+        assert!(
+            close_accel > far_accel,
+            "closer neighbor should push harder: close={close_accel}, far={far_accel}"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn apply_gravity_pulls_equal_masses_together_symmetrically() {
+        let mut system = PositionSystemSIMD::new(8);
+        system.add_entity(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        system.add_entity(10.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+
+        system.apply_gravity(1.0, 0.1);
+
+        let acc = system.get_acceleration_data();
+        assert!(acc[0] > 0.0, "body 0 should accelerate toward body 1");
+        assert!(acc[2] < 0.0, "body 1 should accelerate toward body 0");
+        assert!((acc[0] + acc[2]).abs() < 1e-5, "equal masses pull with equal magnitude");
+    }
+}